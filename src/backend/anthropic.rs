@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{BranchGenerator, GenParams};
+use crate::format::FormatSpec;
+
+/// Generates branch names via the Anthropic Messages API. Anthropic has no
+/// JSON-schema response mode, so we force a single tool call instead and
+/// read the branch name back out of its input.
+pub struct AnthropicBackend {
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+}
+
+impl AnthropicBackend {
+    pub fn new(model: String) -> Result<Self> {
+        let api_key = std::env::var("ANTHROPIC_API_KEY")
+            .context("ANTHROPIC_API_KEY must be set to use --backend anthropic")?;
+
+        Ok(AnthropicBackend {
+            client: reqwest::Client::new(),
+            api_key,
+            model,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text { text: String },
+    ToolUse { input: Value },
+    #[serde(other)]
+    Other,
+}
+
+impl BranchGenerator for AnthropicBackend {
+    async fn generate(&self, prompt: &str, opts: &GenParams, format: &FormatSpec) -> Result<String> {
+        let name_schema = format
+            .json_schema()
+            .map(|schema| json!(schema))
+            .unwrap_or_else(|| json!({ "type": "string" }));
+
+        let body = json!({
+            "model": self.model,
+            "max_tokens": 64,
+            "temperature": opts.temperature,
+            "top_p": opts.top_p,
+            "top_k": opts.top_k,
+            "messages": [{ "role": "user", "content": prompt }],
+            "tools": [{
+                "name": "branch_name",
+                "description": "Record the chosen branch name.",
+                "input_schema": {
+                    "type": "object",
+                    "properties": { "name": name_schema },
+                    "required": ["name"],
+                },
+            }],
+            "tool_choice": { "type": "tool", "name": "branch_name" },
+        });
+
+        let response: MessagesResponse = self
+            .client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let input = response
+            .content
+            .into_iter()
+            .find_map(|block| match block {
+                ContentBlock::ToolUse { input } => Some(input),
+                _ => None,
+            })
+            .context("Anthropic response contained no tool call")?;
+
+        let name = input
+            .get("name")
+            .context("Anthropic tool call was missing `name`")?;
+
+        Ok(name.to_string())
+    }
+}
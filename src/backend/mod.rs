@@ -0,0 +1,103 @@
+//! Pluggable generation backends.
+//!
+//! `jj-gpc` started out hardwired to a local Ollama instance. [`ValidModel`]
+//! is the seam that lets it talk to other providers instead: each variant
+//! wraps a concrete backend and knows how to translate the structured
+//! branch-name constraint into whatever constrained-decoding mechanism that
+//! provider supports (Ollama's `format`, OpenAI's JSON schema mode, or an
+//! Anthropic tool call).
+
+mod anthropic;
+mod ollama;
+mod openai;
+
+use anyhow::Result;
+
+pub use anthropic::AnthropicBackend;
+pub use ollama::OllamaBackend;
+pub use openai::OpenAiBackend;
+
+use crate::format::FormatSpec;
+
+/// Sampling and decoding parameters shared across backends.
+///
+/// These mirror the CLI flags on [`crate::GenerateArgs`]; each backend translates the
+/// subset it understands into its own request shape and ignores the rest.
+/// Most of the extended fields here (`mirostat*`, `repeat_penalty`, `num_ctx`,
+/// `num_predict`) are Ollama-specific knobs and are simply ignored by the
+/// hosted backends.
+#[derive(Debug, Clone)]
+pub struct GenParams {
+    pub temperature: f32,
+    pub top_k: u32,
+    pub top_p: f32,
+    /// Seed for reproducible generations, e.g. for `--dry-run` or CI.
+    pub seed: Option<i32>,
+    pub repeat_penalty: Option<f32>,
+    pub num_ctx: Option<u64>,
+    pub num_predict: Option<i32>,
+    /// Mirostat mode: 0 disables it, 1 is Mirostat, 2 is Mirostat 2.0.
+    pub mirostat: Option<u8>,
+    pub mirostat_tau: Option<f32>,
+    pub mirostat_eta: Option<f32>,
+}
+
+/// Something that can turn a commit-log prompt into a candidate branch name.
+///
+/// Implementations are expected to constrain decoding to `format`'s JSON
+/// schema where they can, so the result comes back as a bare JSON string
+/// rather than relying on the model to behave. `format.json_schema()` is
+/// `None` for a custom grammar with no schema-based decoding support; the backend should
+/// just send the prompt as-is and let client-side regex validation catch a
+/// misbehaving response.
+pub trait BranchGenerator {
+    async fn generate(&self, prompt: &str, opts: &GenParams, format: &FormatSpec) -> Result<String>;
+}
+
+/// Which provider to generate branch names with, selected via `--backend`
+/// or the `backend` key in [`crate::config::Config`].
+#[derive(clap::ValueEnum, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    Ollama,
+    OpenAi,
+    Anthropic,
+}
+
+/// A constructed, ready-to-use backend for one of the [`Backend`] providers.
+pub enum ValidModel {
+    Ollama(OllamaBackend),
+    OpenAi(OpenAiBackend),
+    Anthropic(AnthropicBackend),
+}
+
+impl ValidModel {
+    /// Build the backend selected by `--backend`, using `model` or its
+    /// provider-specific default if `None`. `host` and `port` select the
+    /// Ollama instance to talk to and are ignored by the other backends.
+    pub fn new(backend: Backend, model: Option<String>, host: &str, port: u16) -> Result<Self> {
+        Ok(match backend {
+            Backend::Ollama => ValidModel::Ollama(OllamaBackend::new(
+                model.unwrap_or_else(|| "phi3".into()),
+                host,
+                port,
+            )),
+            Backend::OpenAi => ValidModel::OpenAi(OpenAiBackend::new(
+                model.unwrap_or_else(|| "gpt-4o-mini".into()),
+            )?),
+            Backend::Anthropic => ValidModel::Anthropic(AnthropicBackend::new(
+                model.unwrap_or_else(|| "claude-3-5-haiku-latest".into()),
+            )?),
+        })
+    }
+}
+
+impl BranchGenerator for ValidModel {
+    async fn generate(&self, prompt: &str, opts: &GenParams, format: &FormatSpec) -> Result<String> {
+        match self {
+            ValidModel::Ollama(backend) => backend.generate(prompt, opts, format).await,
+            ValidModel::OpenAi(backend) => backend.generate(prompt, opts, format).await,
+            ValidModel::Anthropic(backend) => backend.generate(prompt, opts, format).await,
+        }
+    }
+}
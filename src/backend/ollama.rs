@@ -0,0 +1,91 @@
+use anyhow::Result;
+use ollama_rs::{
+    generation::{
+        completion::{request::GenerationRequest, GenerationResponse},
+        options::GenerationOptions,
+        parameters::{FormatType, JsonStructure},
+    },
+    Ollama,
+};
+
+use crate::format::{FormatKind, FormatSpec, JiraPrefixBranch, KebabBranch, ScopedBranch};
+
+use super::{BranchGenerator, GenParams};
+
+/// Generates branch names with a local (or remote) Ollama instance,
+/// constraining output with Ollama's structured-output `format`.
+pub struct OllamaBackend {
+    client: Ollama,
+    model: String,
+}
+
+impl OllamaBackend {
+    /// Build a backend for the Ollama instance at `host:port`, e.g. a GPU
+    /// box or a shared server rather than the local default.
+    pub fn new(model: String, host: &str, port: u16) -> Self {
+        OllamaBackend {
+            client: Ollama::new(host.to_string(), port),
+            model,
+        }
+    }
+}
+
+impl BranchGenerator for OllamaBackend {
+    async fn generate(&self, prompt: &str, opts: &GenParams, format: &FormatSpec) -> Result<String> {
+        let mut options = GenerationOptions::default()
+            .top_k(opts.top_k)
+            .top_p(opts.top_p)
+            .temperature(opts.temperature);
+
+        if let Some(seed) = opts.seed {
+            options = options.seed(seed);
+        }
+        if let Some(repeat_penalty) = opts.repeat_penalty {
+            options = options.repeat_penalty(repeat_penalty);
+        }
+        if let Some(num_ctx) = opts.num_ctx {
+            options = options.num_ctx(num_ctx);
+        }
+        if let Some(num_predict) = opts.num_predict {
+            options = options.num_predict(num_predict);
+        }
+        if let Some(mirostat) = opts.mirostat {
+            options = options.mirostat(mirostat);
+        }
+        if let Some(mirostat_tau) = opts.mirostat_tau {
+            options = options.mirostat_tau(mirostat_tau);
+        }
+        if let Some(mirostat_eta) = opts.mirostat_eta {
+            options = options.mirostat_eta(mirostat_eta);
+        }
+
+        let mut request = GenerationRequest::new(self.model.clone(), prompt.to_string());
+        request = match format.kind {
+            FormatKind::Kebab => {
+                request.format(FormatType::StructuredJson(JsonStructure::new::<KebabBranch>()))
+            }
+            FormatKind::JiraPrefix => request.format(FormatType::StructuredJson(
+                JsonStructure::new::<JiraPrefixBranch>(),
+            )),
+            FormatKind::Scoped => {
+                request.format(FormatType::StructuredJson(JsonStructure::new::<ScopedBranch>()))
+            }
+            // Ollama has no raw-GBNF grammar support, so a custom format
+            // relies entirely on the prompt and client-side regex checking.
+            FormatKind::Custom(_) => request,
+        };
+        let request = request.options(options);
+
+        let GenerationResponse { response, .. } = self.client.generate(request).await?;
+
+        // With a schema, Ollama's structured output already comes back as
+        // valid JSON (a bare quoted string, per our schemas). Without one
+        // (a custom grammar), `response` is raw free-form text, so it needs
+        // to be JSON-encoded before the caller's `serde_json::from_str`.
+        if format.json_schema().is_some() {
+            Ok(response)
+        } else {
+            Ok(serde_json::to_string(&response)?)
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{BranchGenerator, GenParams};
+use crate::format::FormatSpec;
+
+/// Generates branch names via the OpenAI chat completions API, constraining
+/// output with `response_format: json_schema` built from the resolved
+/// `--format`'s JSON schema, when it has one.
+pub struct OpenAiBackend {
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+}
+
+impl OpenAiBackend {
+    pub fn new(model: String) -> Result<Self> {
+        let api_key = std::env::var("OPENAI_API_KEY")
+            .context("OPENAI_API_KEY must be set to use --backend open-ai")?;
+
+        Ok(OpenAiBackend {
+            client: reqwest::Client::new(),
+            api_key,
+            model,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatCompletion {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    content: String,
+}
+
+impl BranchGenerator for OpenAiBackend {
+    async fn generate(&self, prompt: &str, opts: &GenParams, format: &FormatSpec) -> Result<String> {
+        let mut body = json!({
+            "model": self.model,
+            "temperature": opts.temperature,
+            "top_p": opts.top_p,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+
+        if let Some(schema) = format.json_schema() {
+            body["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": "branch",
+                    "schema": schema,
+                    "strict": true,
+                },
+            });
+        }
+
+        if let Some(seed) = opts.seed {
+            body["seed"] = json!(seed);
+        }
+
+        let completion: ChatCompletion = self
+            .client
+            .post("https://api.openai.com/v1/chat/completions")
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let message = completion
+            .choices
+            .into_iter()
+            .next()
+            .context("OpenAI response contained no choices")?
+            .message;
+
+        // With a schema, `response_format: json_schema` already gives us
+        // valid JSON (a bare quoted string, per our schemas). Without one
+        // (a custom grammar), `content` is raw free-form text, so it needs
+        // to be JSON-encoded before the caller's `serde_json::from_str`.
+        if format.json_schema().is_some() {
+            Ok(message.content)
+        } else {
+            Ok(serde_json::to_string(&message.content)?)
+        }
+    }
+}
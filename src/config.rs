@@ -0,0 +1,121 @@
+//! Layered configuration: built-in defaults, overridden by
+//! `~/.config/jj-gpc/config.toml`, overridden by a repo-local `.jj-gpc.toml`,
+//! overridden in turn by whatever flags were actually passed on the CLI.
+//!
+//! Every field is optional here; [`Config`] only records what a file
+//! *overrides*. Resolving the final value for each setting is left to the
+//! caller, which knows the CLI-flag value and the hard-coded default too.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::backend::Backend;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    pub model: Option<String>,
+    pub backend: Option<Backend>,
+    pub prefix: Option<String>,
+    pub from: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub temperature: Option<f32>,
+    pub top_k: Option<u32>,
+    pub top_p: Option<f32>,
+    pub seed: Option<i32>,
+    pub repeat_penalty: Option<f32>,
+    pub num_ctx: Option<u64>,
+    pub num_predict: Option<i32>,
+    pub mirostat: Option<u8>,
+    pub mirostat_tau: Option<f32>,
+    pub mirostat_eta: Option<f32>,
+    pub format: Option<String>,
+    pub branch_regex: Option<String>,
+    pub prompt_start: Option<String>,
+    pub prompt_end: Option<String>,
+}
+
+impl Config {
+    /// Load and merge the user config and repo-local config, in that order,
+    /// so that repo-local settings win. Missing files are not an error;
+    /// malformed ones are.
+    pub fn load() -> Result<Config> {
+        let mut config = Config::default();
+
+        if let Some(path) = user_config_path() {
+            if let Some(user) = Config::read(&path)? {
+                config.merge(user);
+            }
+        }
+
+        if let Some(repo) = Config::read(Path::new(".jj-gpc.toml"))? {
+            config.merge(repo);
+        }
+
+        Ok(config)
+    }
+
+    fn read(path: &Path) -> Result<Option<Config>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .with_context(|| format!("invalid config at {}", path.display()))
+                .map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("could not read {}", path.display())),
+        }
+    }
+
+    /// Overwrite any field `other` sets, leaving the rest as-is.
+    fn merge(&mut self, other: Config) {
+        let Config {
+            model,
+            backend,
+            prefix,
+            from,
+            host,
+            port,
+            temperature,
+            top_k,
+            top_p,
+            seed,
+            repeat_penalty,
+            num_ctx,
+            num_predict,
+            mirostat,
+            mirostat_tau,
+            mirostat_eta,
+            format,
+            branch_regex,
+            prompt_start,
+            prompt_end,
+        } = other;
+
+        self.model = model.or(self.model.take());
+        self.backend = backend.or(self.backend.take());
+        self.prefix = prefix.or(self.prefix.take());
+        self.from = from.or(self.from.take());
+        self.host = host.or(self.host.take());
+        self.port = port.or(self.port.take());
+        self.temperature = temperature.or(self.temperature.take());
+        self.top_k = top_k.or(self.top_k.take());
+        self.top_p = top_p.or(self.top_p.take());
+        self.seed = seed.or(self.seed.take());
+        self.repeat_penalty = repeat_penalty.or(self.repeat_penalty.take());
+        self.num_ctx = num_ctx.or(self.num_ctx.take());
+        self.num_predict = num_predict.or(self.num_predict.take());
+        self.mirostat = mirostat.or(self.mirostat.take());
+        self.mirostat_tau = mirostat_tau.or(self.mirostat_tau.take());
+        self.mirostat_eta = mirostat_eta.or(self.mirostat_eta.take());
+        self.format = format.or(self.format.take());
+        self.branch_regex = branch_regex.or(self.branch_regex.take());
+        self.prompt_start = prompt_start.or(self.prompt_start.take());
+        self.prompt_end = prompt_end.or(self.prompt_end.take());
+    }
+}
+
+fn user_config_path() -> Option<std::path::PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("jj-gpc").join("config.toml"))
+}
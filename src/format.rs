@@ -0,0 +1,123 @@
+//! Output-format specs for generated branch names.
+//!
+//! `Branch` started out hardwiring one regex and one JSON schema. A
+//! `--format` flag now selects a named preset — `kebab` (the original
+//! default), `jira-prefix`, or `scoped` — or a path to a custom GBNF
+//! grammar file, so teams can enforce their own branch-naming conventions.
+//! Presets carry both a JSON schema (for backends with JSON-schema-style
+//! constrained decoding) and a regex used to validate the result
+//! client-side before `jj bookmark create` ever runs.
+
+use std::fs;
+
+use anyhow::{Context, Result};
+use ollama_rs::generation::parameters::JsonSchema;
+use regex::Regex;
+use schemars::{schema::RootSchema, schema_for};
+use serde::Deserialize;
+
+/// Which preset's JSON schema to hand a backend, or a custom grammar with
+/// no schema-based decoding support (yet) of its own.
+#[derive(Debug, Clone)]
+pub enum FormatKind {
+    Kebab,
+    JiraPrefix,
+    Scoped,
+    /// A user-supplied GBNF grammar. No backend here speaks raw GBNF, so it
+    /// is folded into the prompt as guidance instead of a decoding
+    /// constraint; `FormatSpec::regex` is what actually gets enforced.
+    Custom(String),
+}
+
+/// A resolved `--format`: how to constrain decoding, and how to validate
+/// the result.
+#[derive(Debug, Clone)]
+pub struct FormatSpec {
+    pub name: String,
+    pub kind: FormatKind,
+    pub regex: Regex,
+}
+
+impl FormatSpec {
+    /// Resolve `--format`. `kebab`, `jira-prefix`, and `scoped` are
+    /// built-in presets; anything else is treated as a path to a `.gbnf`
+    /// grammar file. Custom grammars need a `branch-regex` in the config
+    /// file, since there's no GBNF parser here to derive one automatically.
+    /// `branch_regex` is ignored for a named preset: the preset's regex and
+    /// its JSON schema (see [`FormatSpec::json_schema`]) are baked into the
+    /// same marker type, so overriding one without the other would let a
+    /// backend constrain decoding to one pattern while the client validates
+    /// against another.
+    pub fn resolve(format: &str, branch_regex: Option<&str>) -> Result<FormatSpec> {
+        let preset_regex = |pattern: &str| Regex::new(pattern).expect("preset regex is valid");
+
+        let spec = match format {
+            "kebab" => FormatSpec {
+                name: "kebab".into(),
+                kind: FormatKind::Kebab,
+                regex: preset_regex(KEBAB_REGEX),
+            },
+            "jira-prefix" => FormatSpec {
+                name: "jira-prefix".into(),
+                kind: FormatKind::JiraPrefix,
+                regex: preset_regex(JIRA_PREFIX_REGEX),
+            },
+            "scoped" => FormatSpec {
+                name: "scoped".into(),
+                kind: FormatKind::Scoped,
+                regex: preset_regex(SCOPED_REGEX),
+            },
+            path => {
+                let grammar = fs::read_to_string(path)
+                    .with_context(|| format!("could not read --format grammar at {path}"))?;
+                let pattern = branch_regex.context(
+                    "a custom --format grammar needs a `branch-regex` in the config file, \
+                     since there's no GBNF parser here to derive one",
+                )?;
+                FormatSpec {
+                    name: path.to_string(),
+                    kind: FormatKind::Custom(grammar),
+                    regex: Regex::new(pattern)
+                        .with_context(|| format!("invalid `branch-regex` {pattern:?}"))?,
+                }
+            }
+        };
+
+        Ok(spec)
+    }
+
+    /// The JSON schema to send a backend for constrained decoding, or
+    /// `None` for a custom grammar (no native schema support).
+    pub fn json_schema(&self) -> Option<RootSchema> {
+        match self.kind {
+            FormatKind::Kebab => Some(schema_for!(KebabBranch)),
+            FormatKind::JiraPrefix => Some(schema_for!(JiraPrefixBranch)),
+            FormatKind::Scoped => Some(schema_for!(ScopedBranch)),
+            FormatKind::Custom(_) => None,
+        }
+    }
+}
+
+const KEBAB_REGEX: &str = "^[a-z]{1,10}+(-[a-z]{1,10}){2,4}$";
+const JIRA_PREFIX_REGEX: &str = "^[A-Z]{2,10}-[0-9]+/[a-z]{1,10}(-[a-z]{1,10}){0,3}$";
+const SCOPED_REGEX: &str = "^[a-z]{1,15}/[a-z]{1,15}/[a-z]{1,10}(-[a-z]{1,10}){0,4}$";
+
+/// Schemars-backed marker types, one per preset, used to build a backend's
+/// JSON schema. `pub(crate)` so the Ollama backend can also pass them to
+/// `JsonStructure::new::<T>()`, which needs a concrete compile-time type.
+#[repr(transparent)]
+#[derive(JsonSchema, Deserialize, Debug)]
+pub(crate) struct KebabBranch(#[schemars(regex(pattern = "^[a-z]{1,10}+(-[a-z]{1,10}){2,4}$"))] String);
+
+#[repr(transparent)]
+#[derive(JsonSchema, Deserialize, Debug)]
+pub(crate) struct JiraPrefixBranch(
+    #[schemars(regex(pattern = "^[A-Z]{2,10}-[0-9]+/[a-z]{1,10}(-[a-z]{1,10}){0,3}$"))] String,
+);
+
+#[repr(transparent)]
+#[derive(JsonSchema, Deserialize, Debug)]
+pub(crate) struct ScopedBranch(
+    #[schemars(regex(pattern = "^[a-z]{1,15}/[a-z]{1,15}/[a-z]{1,10}(-[a-z]{1,10}){0,4}$"))]
+    String,
+);
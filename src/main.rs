@@ -1,22 +1,64 @@
-use std::process;
-
-use clap::Parser as _;
-use ollama_rs::{
-    generation::{
-        completion::{request::GenerationRequest, GenerationResponse},
-        options::GenerationOptions,
-        parameters::{FormatType, JsonSchema, JsonStructure},
-    },
-    Ollama,
-};
-use serde::Deserialize;
+mod backend;
+mod config;
+mod format;
+
+use std::{collections::HashSet, fs, path::Path, process};
+
+use clap::{CommandFactory as _, Parser as _};
+use clap_complete::Shell;
+use rand::Rng as _;
+use regex::Regex;
+
+use backend::{Backend, BranchGenerator, GenParams, ValidModel};
+use config::Config;
+use format::FormatSpec;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
     env_logger::init();
 
-    let args = Cli::parse();
-    let revset = format!("{}..{}", args.from, args.change);
+    match Cli::parse_from(args_with_default_subcommand()).command {
+        Command::Generate(args) => generate(args).await,
+        Command::InstallHook(args) => install_hook(args).unwrap_or_else(|e| panic!("{e}")),
+        Command::Completions { shell } => print_completions(shell),
+    }
+}
+
+/// `generate` is the default subcommand: `jj-gpc --dry-run` should work
+/// exactly like `jj-gpc generate --dry-run`, without clap treating
+/// `--dry-run` as an attempt to select a subcommand named that.
+fn args_with_default_subcommand() -> Vec<std::ffi::OsString> {
+    const SUBCOMMANDS: &[&str] = &[
+        "generate",
+        "install-hook",
+        "completions",
+        "help",
+        "-h",
+        "--help",
+        "-V",
+        "--version",
+    ];
+
+    let mut args: Vec<_> = std::env::args_os().collect();
+    let is_known = args
+        .get(1)
+        .and_then(|arg| arg.to_str())
+        .is_some_and(|arg| SUBCOMMANDS.contains(&arg));
+
+    if !is_known {
+        args.insert(1, "generate".into());
+    }
+
+    args
+}
+
+/// Generate a branch name from the commit log and create (and push) it as
+/// a bookmark. This is the default when no subcommand is given.
+async fn generate(args: GenerateArgs) {
+    let config = Config::load().unwrap_or_else(|e| panic!("{e}"));
+
+    let from = args.from.or(config.from).unwrap_or_else(|| "trunk()".into());
+    let revset = format!("{from}..{}", args.change);
 
     let log_template = match args.log_format {
         Some(LogFormat::Full) => LOG_FULL,
@@ -37,37 +79,84 @@ async fn main() {
         std::process::exit(1);
     }
 
+    let format_name = args.format.or(config.format).unwrap_or_else(|| "kebab".into());
+    let branch_format = FormatSpec::resolve(&format_name, config.branch_regex.as_deref())
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    let prompt_start = config.prompt_start.as_deref().unwrap_or(PROMPT_START);
+    let prompt_end = config.prompt_end.as_deref().unwrap_or(PROMPT_END);
+    let grammar_note = match &branch_format.kind {
+        format::FormatKind::Custom(grammar) => {
+            format!("\n\nThe branch name must conform to this grammar:\n\n{grammar}\n")
+        }
+        _ => String::new(),
+    };
     let prompt = format!(
-        "{PROMPT_START}\n\n{commits}\n\n{PROMPT_END}",
+        "{prompt_start}{grammar_note}\n\n{commits}\n\n{prompt_end}",
         commits = commits.stdout
     );
     log::debug!("prompt: {prompt}");
 
-    let request = GenerationRequest::new(args.model.clone(), prompt.clone())
-        .format(FormatType::StructuredJson(JsonStructure::new::<Branch>()))
-        .options(
-            GenerationOptions::default()
-                .top_k(args.top_k)
-                .top_p(args.top_p)
-                .temperature(args.temperature),
-        );
+    let backend = args.backend.or(config.backend).unwrap_or(Backend::Ollama);
+    let model_name = args.model.clone().or(config.model);
+    let host = args
+        .host
+        .or(config.host)
+        .unwrap_or_else(|| "http://localhost".into());
+    let port = args.port.or(config.port).unwrap_or(11434);
+    let model =
+        ValidModel::new(backend, model_name, &host, port).unwrap_or_else(|e| panic!("{e}"));
+
+    let opts = GenParams {
+        temperature: args.temperature.or(config.temperature).unwrap_or(2.0),
+        top_k: args.top_k.or(config.top_k).unwrap_or(20),
+        top_p: args.top_p.or(config.top_p).unwrap_or(0.7),
+        seed: args.seed.or(config.seed),
+        repeat_penalty: args.repeat_penalty.or(config.repeat_penalty),
+        num_ctx: args.num_ctx.or(config.num_ctx),
+        num_predict: args.num_predict.or(config.num_predict),
+        mirostat: args.mirostat.or(config.mirostat),
+        mirostat_tau: args.mirostat_tau.or(config.mirostat_tau),
+        mirostat_eta: args.mirostat_eta.or(config.mirostat_eta),
+    };
 
-    let response_result = Ollama::default()
-        .generate(request)
+    let response = model
+        .generate(&prompt, &opts, &branch_format)
         .await
         .unwrap_or_else(|e| panic!("{e}"));
 
-    let GenerationResponse { response, .. } = response_result;
-
-    let Branch(branch) = serde_json::from_str::<Branch>(&response).unwrap_or_else(|err| {
+    let branch = serde_json::from_str::<String>(&response).unwrap_or_else(|err| {
         eprintln!("{err}");
         process::exit(1);
     });
 
-    let branch_name = args
-        .prefix
-        .map(|prefix| format!("{prefix}/{}", branch))
-        .unwrap_or(branch);
+    if !branch_format.regex.is_match(&branch) {
+        eprintln!(
+            "Generated branch name {branch:?} does not match the `{}` format",
+            branch_format.name
+        );
+        process::exit(1);
+    }
+
+    let prefix = args.prefix.or(config.prefix);
+    let full_name = |branch: &str| -> String {
+        prefix
+            .as_ref()
+            .map(|prefix| format!("{prefix}/{branch}"))
+            .unwrap_or_else(|| branch.to_string())
+    };
+
+    let branch_name = if args.no_unique {
+        full_name(&branch)
+    } else {
+        let existing = list_bookmarks();
+        unique_branch_name(&branch, &branch_format.regex, &existing, &full_name).unwrap_or_else(
+            |e| {
+                eprintln!("{e}");
+                process::exit(1);
+            },
+        )
+    };
 
     if args.dry_run {
         println!(
@@ -109,9 +198,161 @@ async fn main() {
     push_output.to_console();
 }
 
-#[repr(transparent)]
-#[derive(JsonSchema, Deserialize, Debug)]
-struct Branch(#[schemars(regex(pattern = "^[a-z]{1,10}+(-[a-z]{1,10}){2,4}$"))] String);
+/// Install a hook that runs `jj-gpc` after every commit, similar to
+/// git-sumi's hook installer. `jj` itself has no hook mechanism yet, and the
+/// only commit-adjacent hook git offers is `post-commit`, so this targets
+/// the `.git` directory of a colocated `jj`/`git` repo and fires there —
+/// not specifically on bookmark creation, but on every commit, same as
+/// running `jj-gpc` by hand after `jj commit`.
+///
+/// Defaults to installing the `--dry-run` script: a hook that silently
+/// creates and pushes a bookmark on every future commit, unattended, is a
+/// much bigger foot-gun than a one-off foreground invocation, so `--run` is
+/// required to opt into the pushing variant.
+fn install_hook(args: InstallHookArgs) -> anyhow::Result<()> {
+    let hooks_dir = Path::new(".git/hooks");
+    if !hooks_dir.is_dir() {
+        anyhow::bail!(
+            "no .git/hooks directory found; `install-hook` only works in a colocated jj/git \
+             repo (`jj git init --colocate`), since jj has no native hook mechanism yet"
+        );
+    }
+
+    let hook_path = hooks_dir.join("post-commit");
+    if hook_path.exists() && !args.force {
+        anyhow::bail!(
+            "{} already exists; pass --force to overwrite it (it will be backed up to {}.bak first)",
+            hook_path.display(),
+            hook_path.display()
+        );
+    }
+
+    if hook_path.exists() {
+        let backup_path = hooks_dir.join("post-commit.bak");
+        fs::copy(&hook_path, &backup_path)?;
+        println!("Backed up existing {} to {}", hook_path.display(), backup_path.display());
+    }
+
+    let hook_script = if args.run {
+        POST_COMMIT_HOOK
+    } else {
+        POST_COMMIT_HOOK_DRY_RUN
+    };
+    fs::write(&hook_path, hook_script)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    println!("Installed {}", hook_path.display());
+    Ok(())
+}
+
+/// Runs `jj-gpc` for real after every commit: generates a branch name and
+/// creates and pushes it. Installed only by `install-hook --run`.
+const POST_COMMIT_HOOK: &str = "#!/bin/sh\nexec jj-gpc\n";
+/// Only prints the commands `jj-gpc` would run. The default script for
+/// `install-hook`, since pushing unattended on every commit is not a safe
+/// default for a hook installed once and then forgotten about.
+const POST_COMMIT_HOOK_DRY_RUN: &str = "#!/bin/sh\nexec jj-gpc --dry-run\n";
+
+/// Emit a clap-generated completion script for `shell` on stdout, e.g.
+/// `jj-gpc completions zsh > ~/.zfunc/_jj-gpc`.
+fn print_completions(shell: Shell) {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, &mut std::io::stdout());
+}
+
+/// List the names of bookmarks that already exist in the repo, so a freshly
+/// generated name can be checked for collisions before `jj bookmark create`
+/// either fails or clobbers existing intent.
+fn list_bookmarks() -> HashSet<String> {
+    let output = execute(process::Command::new("jj").args(&[
+        "bookmark",
+        "list",
+        "-T",
+        r#"name ++ "\n""#,
+    ]));
+
+    output
+        .stdout
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Disambiguate `branch` against `existing` bookmark names (formatted with
+/// `full_name`, which applies `--prefix`) by appending a short random
+/// lowercase token, borrowing triagebot's "append random sequence to make it
+/// unique" approach. Falls back to an incrementing letter suffix (`-a`,
+/// `-b`, ..., `-z`, `-aa`, ...) if no random suffix keeps the result
+/// matching `branch_regex` after a few tries; every built-in format is
+/// letters-only, so a numeric suffix would never pass that check.
+///
+/// Both suffix strategies add a whole extra hyphen-separated word, so if
+/// `branch` is already at its format's word-count ceiling (a 5-word kebab
+/// name, say), no suffixed candidate can ever match `branch_regex` either —
+/// there's no generic way to tell a preset's regex "replace the last word"
+/// instead. Rather than loop forever in that case, the counter fallback is
+/// bounded; exhausting it is reported as an error instead of hanging.
+fn unique_branch_name(
+    branch: &str,
+    branch_regex: &Regex,
+    existing: &HashSet<String>,
+    full_name: impl Fn(&str) -> String,
+) -> Result<String, String> {
+    let candidate = full_name(branch);
+    if !existing.contains(&candidate) {
+        return Ok(candidate);
+    }
+
+    for _ in 0..20 {
+        let suffix: String = (0..4)
+            .map(|_| (b'a' + rand::thread_rng().gen_range(0..26)) as char)
+            .collect();
+        let candidate_branch = format!("{branch}-{suffix}");
+        let candidate = full_name(&candidate_branch);
+        if branch_regex.is_match(&candidate_branch) && !existing.contains(&candidate) {
+            return Ok(candidate);
+        }
+    }
+
+    const MAX_COUNTER_ATTEMPTS: u64 = 1_000;
+    for n in 0..MAX_COUNTER_ATTEMPTS {
+        let candidate_branch = format!("{branch}-{}", letter_suffix(n));
+        let candidate = full_name(&candidate_branch);
+        if branch_regex.is_match(&candidate_branch) && !existing.contains(&candidate) {
+            return Ok(candidate);
+        }
+    }
+
+    Err(format!(
+        "could not find a unique name for `{branch}` that still matches its format after \
+         20 random and {MAX_COUNTER_ATTEMPTS} counter suffixes; try `--no-unique` or a \
+         different `--format`"
+    ))
+}
+
+/// Bijective base-26 counter: 0 -> "a", 25 -> "z", 26 -> "aa", ... Unlike a
+/// plain decimal counter, every output is letters-only, so it stays valid
+/// against letters-only `branch_regex` patterns like the built-in presets.
+fn letter_suffix(mut n: u64) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(b'a' + (n % 26) as u8);
+        if n < 26 {
+            break;
+        }
+        n = n / 26 - 1;
+    }
+    letters.reverse();
+    String::from_utf8(letters).expect("only ever pushes ASCII lowercase letters")
+}
 
 fn execute(command: &mut process::Command) -> CommandOutput {
     log::trace!("{command:?}");
@@ -155,18 +396,57 @@ impl CommandOutput {
     }
 }
 
-/// Generate a branch name for use with jj.
 #[derive(clap::Parser, Debug)]
 #[command(version, author)]
 struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Generate a branch name for use with jj. (default)
+    Generate(GenerateArgs),
+
+    /// Install a git hook that runs `jj-gpc --dry-run` after each commit,
+    /// for colocated jj/git repos. Pass `--run` to install one that actually
+    /// creates and pushes a bookmark.
+    InstallHook(InstallHookArgs),
+
+    /// Emit a shell completion script on stdout.
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+}
+
+/// Install a git hook that runs `jj-gpc` after each commit.
+#[derive(clap::Args, Debug)]
+struct InstallHookArgs {
+    /// Overwrite an existing `post-commit` hook instead of refusing to.
+    /// The existing hook is backed up to `post-commit.bak` first.
+    #[arg(long, default_value = "false")]
+    force: bool,
+
+    /// Install a hook that actually creates and pushes the generated
+    /// bookmark on every commit, instead of the default, which only prints
+    /// the commands it would run.
+    #[arg(long, default_value = "false")]
+    run: bool,
+}
+
+/// Generate a branch name for use with jj.
+#[derive(clap::Args, Debug)]
+struct GenerateArgs {
     #[arg(default_value = "@")]
     change: String,
 
     #[arg(long, value_enum)]
     log_format: Option<LogFormat>,
 
-    #[arg(short, long, default_value = "trunk()")]
-    from: String,
+    /// Defaults to `trunk()`, or the `from` key in the config file.
+    #[arg(short, long)]
+    from: Option<String>,
 
     /// Prefix for the generated branch name, `<prefix>/<generated>`
     #[arg(short, long)]
@@ -176,30 +456,99 @@ struct Cli {
     #[arg(long = "dry-run", default_value = "false")]
     dry_run: bool,
 
+    /// Skip collision detection: create the bookmark with the generated
+    /// name even if one by that name already exists.
+    #[arg(long = "no-unique", default_value = "false")]
+    no_unique: bool,
+
     /// The temperature of the model. Increasing the temperature will make the
-    /// model answer more creatively.
-    #[arg(long, default_value = "2")]
-    temperature: f32,
+    /// model answer more creatively. Defaults to 2, or the `temperature` key
+    /// in the config file.
+    #[arg(long)]
+    temperature: Option<f32>,
 
     /// Reduces the probability of generating nonsense. A higher value (e.g. 100)
     /// will give more diverse answers, while a lower value (e.g. 10) will be more
-    /// conservative. (Default: 40)
-    #[arg(long, default_value = "20")]
-    top_k: u32,
+    /// conservative. Defaults to 20, or the `top-k` key in the config file.
+    #[arg(long)]
+    top_k: Option<u32>,
 
     /// Works together with top-k. A higher value (e.g., 0.95) will lead to more
     /// diverse text, while a lower value (e.g., 0.5) will generate more focused
-    /// and conservative text.
-    #[arg(long, default_value = "0.7")]
-    top_p: f32,
+    /// and conservative text. Defaults to 0.7, or the `top-p` key in the
+    /// config file.
+    #[arg(long)]
+    top_p: Option<f32>,
 
-    /// Which model to use. Can be any model available in Ollama on your system.
+    /// Which model to use. Can be any model available from the chosen
+    /// `--backend`.
     ///
     /// The model you choose to use will significantly alters the quality of the
     /// output, so you may need to tune the parameters as well. If this is not a
-    /// model available in Ollama on your system, the request will fail.
-    #[arg(long, default_value = "phi3")]
-    model: String,
+    /// model available from the backend, the request will fail. Defaults to
+    /// `phi3` for Ollama, `gpt-4o-mini` for OpenAI, and `claude-3-5-haiku-latest`
+    /// for Anthropic.
+    #[arg(long)]
+    model: Option<String>,
+
+    /// Which backend to generate the branch name with. Defaults to Ollama,
+    /// or the `backend` key in the config file.
+    #[arg(long, value_enum)]
+    backend: Option<Backend>,
+
+    /// The branch-naming scheme to generate and validate against: a named
+    /// preset (`kebab`, `jira-prefix`, `scoped`) or a path to a custom
+    /// `.gbnf` grammar file. Defaults to `kebab`, or the `format` key in the
+    /// config file.
+    #[arg(long)]
+    format: Option<String>,
+
+    /// Host of the Ollama instance to use, e.g. a GPU box or shared server.
+    /// Defaults to `http://localhost`, or the `host` key in the config
+    /// file. Ignored for other backends.
+    #[arg(long)]
+    host: Option<String>,
+
+    /// Port of the Ollama instance to use. Defaults to 11434, or the `port`
+    /// key in the config file. Ignored for other backends.
+    #[arg(long)]
+    port: Option<u16>,
+
+    /// Seed for the model's random number generator. Setting this makes
+    /// generation reproducible for the same commits, which is useful for
+    /// `--dry-run` and CI.
+    #[arg(long)]
+    seed: Option<i32>,
+
+    /// Penalizes repeated tokens: higher values (e.g. 1.5) discourage
+    /// repetition more, lower values (e.g. 0.9) are more lenient. (Ollama
+    /// only.)
+    #[arg(long)]
+    repeat_penalty: Option<f32>,
+
+    /// Size of the context window used to generate tokens. (Ollama only.)
+    #[arg(long)]
+    num_ctx: Option<u64>,
+
+    /// Maximum number of tokens to predict when generating text. (Ollama
+    /// only.)
+    #[arg(long)]
+    num_predict: Option<i32>,
+
+    /// Enables Mirostat sampling, which dynamically targets a perplexity
+    /// setpoint instead of relying on `--temperature`. `0` disables it
+    /// (the default), `1` is Mirostat, `2` is Mirostat 2.0. (Ollama only.)
+    #[arg(long)]
+    mirostat: Option<u8>,
+
+    /// Mirostat target entropy (perplexity setpoint). (Ollama only.)
+    #[arg(long)]
+    mirostat_tau: Option<f32>,
+
+    /// Mirostat learning rate: lower values mean slower adjustment. (Ollama
+    /// only.)
+    #[arg(long)]
+    mirostat_eta: Option<f32>,
 }
 
 #[derive(clap::ValueEnum, Debug, Clone)]
@@ -227,3 +576,79 @@ const PROMPT_END: &'static str = r#"
 
 The best descriptive branch name for these commits (*not* a pull request description, just a branch name) for a Git branch containing these commits is:
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letter_suffix_counts_up_like_spreadsheet_columns() {
+        assert_eq!(letter_suffix(0), "a");
+        assert_eq!(letter_suffix(25), "z");
+        assert_eq!(letter_suffix(26), "aa");
+        assert_eq!(letter_suffix(27), "ab");
+        assert_eq!(letter_suffix(51), "az");
+        assert_eq!(letter_suffix(52), "ba");
+        assert_eq!(letter_suffix(701), "zz");
+        assert_eq!(letter_suffix(702), "aaa");
+    }
+
+    #[test]
+    fn letter_suffix_is_always_lowercase_ascii_letters() {
+        for n in 0..1_000 {
+            assert!(letter_suffix(n).bytes().all(|b| b.is_ascii_lowercase()));
+        }
+    }
+
+    fn kebab_regex() -> Regex {
+        Regex::new("^[a-z]{1,10}+(-[a-z]{1,10}){2,4}$").expect("valid regex")
+    }
+
+    #[test]
+    fn unique_branch_name_returns_branch_unchanged_when_not_taken() {
+        let existing = HashSet::new();
+        let name =
+            unique_branch_name("add-login-page", &kebab_regex(), &existing, |s| s.to_string())
+                .expect("no collision");
+        assert_eq!(name, "add-login-page");
+    }
+
+    #[test]
+    fn unique_branch_name_applies_prefix_via_full_name() {
+        let existing = HashSet::new();
+        let name = unique_branch_name("add-login-page", &kebab_regex(), &existing, |s| {
+            format!("me/{s}")
+        })
+        .expect("no collision");
+        assert_eq!(name, "me/add-login-page");
+    }
+
+    #[test]
+    fn unique_branch_name_appends_matching_suffix_on_collision() {
+        let mut existing = HashSet::new();
+        existing.insert("add-login-page".to_string());
+
+        let name =
+            unique_branch_name("add-login-page", &kebab_regex(), &existing, |s| s.to_string())
+                .expect("a letters-only suffix exists");
+        assert!(kebab_regex().is_match(&name));
+        assert_ne!(name, "add-login-page");
+    }
+
+    #[test]
+    fn unique_branch_name_errors_instead_of_hanging_at_the_word_count_ceiling() {
+        // `kebab`'s regex caps out at 5 words; any suffix adds a 6th, so no
+        // candidate can ever match once `branch` itself is already at the
+        // cap and taken. This must return an error, not loop forever.
+        let mut existing = HashSet::new();
+        existing.insert("this-branch-has-five-words".to_string());
+
+        let result = unique_branch_name(
+            "this-branch-has-five-words",
+            &kebab_regex(),
+            &existing,
+            |s| s.to_string(),
+        );
+        assert!(result.is_err());
+    }
+}